@@ -0,0 +1,23 @@
+use crate::error::Error;
+
+// `SysfsBacklight` is the only backend that exists right now, so it's unconditionally compiled
+// in; there is no Cargo feature gating it. If a second backend (DDC-CI, macOS, Windows, ...)
+// is ever added, that's the point to introduce per-OS cargo features and cfg-gate each backend
+// module behind its own — not before, since a single always-on implementation has nothing to
+// select between.
+mod sysfs;
+pub(crate) use sysfs::SysfsBacklight;
+
+/// A controllable brightness source. Only the embedded panel via sysfs is implemented today;
+/// the trait exists so a future external-monitor (DDC-CI) or macOS/Windows backend can share the
+/// same `Action` logic without sysfs-specific code leaking into it.
+pub(crate) trait Backlight {
+    /// The current brightness, in the same units as [`Backlight::max`].
+    fn get(&self) -> Result<u32, Error>;
+
+    /// The highest brightness value this device accepts.
+    fn max(&self) -> Result<u32, Error>;
+
+    /// Sets the brightness to `value`, which should be in `0..=self.max()?`.
+    fn set(&mut self, value: u32) -> Result<(), Error>;
+}
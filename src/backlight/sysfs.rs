@@ -0,0 +1,26 @@
+use super::Backlight;
+use crate::device::BacklightDevice;
+use crate::error::Error;
+
+/// Controls the embedded panel's backlight through the Linux sysfs interface.
+pub(crate) struct SysfsBacklight(BacklightDevice);
+
+impl SysfsBacklight {
+    pub(crate) fn new(device: BacklightDevice) -> Self {
+        SysfsBacklight(device)
+    }
+}
+
+impl Backlight for SysfsBacklight {
+    fn get(&self) -> Result<u32, Error> {
+        self.0.brightness()
+    }
+
+    fn max(&self) -> Result<u32, Error> {
+        self.0.max_brightness()
+    }
+
+    fn set(&mut self, value: u32) -> Result<(), Error> {
+        self.0.set_brightness(value)
+    }
+}
@@ -0,0 +1,229 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+const BACKLIGHT_CLASS_DIR: &str = "/sys/class/backlight";
+
+/// A backlight device found under `/sys/class/backlight`, e.g. `intel_backlight` or `amdgpu_bl0`.
+#[derive(Debug, Clone)]
+pub(crate) struct BacklightDevice {
+    dir: PathBuf,
+}
+
+impl BacklightDevice {
+    pub(crate) fn name(&self) -> &str {
+        self.dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("?")
+    }
+
+    /// The kernel's `type` for this device: `raw`, `firmware`, `platform`, or similar.
+    pub(crate) fn kind(&self) -> String {
+        read_file_as_string(&self.dir.join("type")).unwrap_or_else(|_| "unknown".to_string())
+    }
+
+    pub(crate) fn max_brightness(&self) -> Result<u32, Error> {
+        read_file_as_u32(&self.dir.join("max_brightness"))
+    }
+
+    pub(crate) fn brightness(&self) -> Result<u32, Error> {
+        read_file_as_u32(&self.dir.join("brightness"))
+    }
+
+    pub(crate) fn set_brightness(&self, n: u32) -> Result<(), Error> {
+        write_file_from_u32(&self.dir.join("brightness"), n)
+    }
+}
+
+/// Enumerates every backlight device the kernel exposes, sorted by name.
+pub(crate) fn discover_devices() -> Result<Vec<BacklightDevice>, Error> {
+    let entries = fs::read_dir(BACKLIGHT_CLASS_DIR).map_err(|cause| {
+        Error::with_cause(format!("could not list {BACKLIGHT_CLASS_DIR}"), cause)
+    })?;
+
+    let mut devices = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|cause| {
+            Error::with_cause(
+                format!("could not read an entry in {BACKLIGHT_CLASS_DIR}"),
+                cause,
+            )
+        })?;
+        devices.push(BacklightDevice { dir: entry.path() });
+    }
+
+    devices.sort_by(|a, b| a.name().cmp(b.name()));
+
+    Ok(devices)
+}
+
+/// Picks the device named by `--device`, or a sensible default when `name` is `None`.
+///
+/// With no explicit name, prefers a raw or firmware device (closest to the hardware) over a
+/// platform/ACPI one, since the latter is sometimes out of sync with the panel's actual range;
+/// ties are broken by device name so the choice is deterministic.
+pub(crate) fn resolve_device(name: Option<&str>) -> Result<BacklightDevice, Error> {
+    let devices = discover_devices()?;
+
+    if devices.is_empty() {
+        return Err(Error::msg(format!(
+            "no backlight devices found under {BACKLIGHT_CLASS_DIR}"
+        )));
+    }
+
+    if let Some(name) = name {
+        return devices
+            .into_iter()
+            .find(|device| device.name() == name)
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "no backlight device named '{name}' (use --list to see available devices)"
+                ))
+            });
+    }
+
+    let kinds: Vec<(&str, String)> = devices
+        .iter()
+        .map(|device| (device.name(), device.kind()))
+        .collect();
+    let candidates: Vec<(&str, &str)> = kinds
+        .iter()
+        .map(|(name, kind)| (*name, kind.as_str()))
+        .collect();
+
+    let best_name = pick_best(&candidates).expect("devices is non-empty").to_string();
+
+    Ok(devices
+        .into_iter()
+        .find(|device| device.name() == best_name)
+        .expect("best_name came from devices"))
+}
+
+/// Picks the preferred device name from `(name, kind)` pairs: raw/firmware devices (closest to
+/// the hardware) win over platform/ACPI ones, with ties broken by name for determinism.
+fn pick_best<'a>(candidates: &[(&'a str, &'a str)]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .min_by(|(name_a, kind_a), (name_b, kind_b)| {
+            kind_priority(kind_a)
+                .cmp(&kind_priority(kind_b))
+                .then_with(|| name_a.cmp(name_b))
+        })
+        .map(|(name, _)| *name)
+}
+
+fn kind_priority(kind: &str) -> u8 {
+    match kind {
+        "raw" => 0,
+        "firmware" => 1,
+        "platform" => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kind_priority_ordering() {
+        assert!(kind_priority("raw") < kind_priority("firmware"));
+        assert!(kind_priority("firmware") < kind_priority("platform"));
+        assert!(kind_priority("platform") < kind_priority("acpi_video0"));
+        assert_eq!(kind_priority("acpi_video0"), kind_priority("totally_unknown"));
+    }
+
+    #[test]
+    fn test_pick_best_prefers_raw_over_firmware_over_platform_over_unknown() {
+        let candidates = [
+            ("acpi_video0", "platform"),
+            ("amdgpu_bl0", "raw"),
+            ("nv_backlight", "firmware"),
+            ("weird0", "something-else"),
+        ];
+        assert_eq!(pick_best(&candidates), Some("amdgpu_bl0"));
+
+        let candidates = [("acpi_video0", "platform"), ("nv_backlight", "firmware")];
+        assert_eq!(pick_best(&candidates), Some("nv_backlight"));
+
+        let candidates = [("acpi_video0", "platform"), ("weird0", "something-else")];
+        assert_eq!(pick_best(&candidates), Some("acpi_video0"));
+    }
+
+    #[test]
+    fn test_pick_best_breaks_ties_by_name() {
+        let candidates = [("zzz_raw", "raw"), ("aaa_raw", "raw")];
+        assert_eq!(pick_best(&candidates), Some("aaa_raw"));
+    }
+
+    #[test]
+    fn test_pick_best_empty_is_none() {
+        assert_eq!(pick_best(&[]), None);
+    }
+}
+
+/// Prints every available backlight device and its current level, for `--list`.
+pub(crate) fn list_devices() -> Result<(), Error> {
+    let devices = discover_devices()?;
+
+    if devices.is_empty() {
+        println!("no backlight devices found under {BACKLIGHT_CLASS_DIR}");
+        return Ok(());
+    }
+
+    for device in &devices {
+        let max = device.max_brightness()?;
+        let current = device.brightness()?;
+        println!("{} ({}): {current}/{max}", device.name(), device.kind());
+    }
+
+    Ok(())
+}
+
+fn read_file_as_string(path: &Path) -> Result<String, Error> {
+    let mut buf = String::with_capacity(16);
+
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(false)
+        .open(path)
+        .map_err(|cause| {
+            Error::with_cause(format!("could not read from {}", path.display()), cause)
+        })?
+        .read_to_string(&mut buf)
+        .map_err(|cause| {
+            Error::with_cause(format!("invalid UTF-8 at {}", path.display()), cause)
+        })?;
+
+    Ok(buf.trim().to_string())
+}
+
+fn read_file_as_u32(path: &Path) -> Result<u32, Error> {
+    let buf = read_file_as_string(path)?;
+
+    str::parse(&buf)
+        .map_err(|cause| Error::with_cause(format!("could not parse '{buf}' as a number"), cause))
+}
+
+fn write_file_from_u32(path: &Path, n: u32) -> Result<(), Error> {
+    write!(
+        std::fs::OpenOptions::new()
+            .read(false)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|cause| Error::with_cause(
+                format!(
+                    "could not open {} for writing; try using `sudo` or running `chmod u+s` on the command or `chmod +w on {}",
+                    path.display(),
+                    path.display()
+                ),
+                cause
+            ))?,
+        "{n}"
+    )
+    .map_err(|cause| Error::with_cause(format!("could not write {n} to {}", path.display()), cause))
+}
@@ -0,0 +1,31 @@
+#[derive(Debug)]
+pub(crate) struct Error {
+    message: String,
+    cause: Option<String>,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(cause) = &self.cause {
+            write!(f, " ({cause})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    pub(crate) fn msg(message: String) -> Self {
+        Error {
+            message,
+            cause: None,
+        }
+    }
+
+    pub(crate) fn with_cause<E: ToString>(message: String, cause: E) -> Self {
+        Error {
+            message,
+            cause: Some(cause.to_string()),
+        }
+    }
+}
@@ -1,23 +1,100 @@
-use std::io::{Read, Write};
+mod backlight;
+mod device;
+mod error;
+mod scale;
 
-const PATH_BRIGHTNESS: &'static str = "/sys/class/backlight/intel_backlight/brightness";
-const PATH_MAX_BRIGHTNESS: &'static str = "/sys/class/backlight/intel_backlight/max_brightness";
+use backlight::{Backlight, SysfsBacklight};
+use error::Error;
+use scale::Scale;
 
 fn main() {
     let args = std::env::args().collect::<Vec<_>>();
     let argv0 = &args[0];
 
-    if args.len() > 2 {
-        usage(argv0);
+    let mut device_name = None;
+    let mut list = false;
+    let mut action_arg = None;
+    let mut scale = Scale::Linear;
+    let mut fade_ms = 0u64;
+    let mut min_percent = DEFAULT_MIN_PERCENT;
+    let mut allow_off = false;
+
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--help" | "-h" => usage(argv0),
+            "--list" => list = true,
+            "--device" => match iter.next() {
+                Some(name) => device_name = Some(name.clone()),
+                None => {
+                    eprintln!("{argv0}: --device requires a device name");
+                    usage(argv0);
+                }
+            },
+            s if s == "--perceptual" || s.starts_with("--perceptual=") => {
+                let gamma = match s.strip_prefix("--perceptual=") {
+                    Some(value) => match value.parse::<f64>() {
+                        Ok(g) => g,
+                        Err(e) => {
+                            eprintln!("{argv0}: could not parse '{value}' as a gamma ({e})");
+                            usage(argv0);
+                        }
+                    },
+                    None => Scale::DEFAULT_GAMMA,
+                };
+
+                scale = Scale::Perceptual { gamma };
+            }
+            "--exponential" => match iter.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(k) if k != 0.0 => scale = Scale::Exponential { k },
+                Some(_) => {
+                    eprintln!("{argv0}: --exponential requires a nonzero K");
+                    usage(argv0);
+                }
+                None => {
+                    eprintln!("{argv0}: --exponential requires a numeric K");
+                    usage(argv0);
+                }
+            },
+            "--fade" => match iter.next().and_then(|s| s.parse::<u64>().ok()) {
+                Some(ms) => fade_ms = ms,
+                None => {
+                    eprintln!("{argv0}: --fade requires a duration in milliseconds");
+                    usage(argv0);
+                }
+            },
+            "--min" => match iter.next().map(|s| parse_percent(s)) {
+                Some(Ok(p)) => min_percent = p,
+                Some(Err(e)) => {
+                    eprintln!("{argv0}: {e}");
+                    usage(argv0);
+                }
+                None => {
+                    eprintln!("{argv0}: --min requires a percentage");
+                    usage(argv0);
+                }
+            },
+            "--allow-off" => allow_off = true,
+            _ if action_arg.is_none() => action_arg = Some(arg.clone()),
+            _ => usage(argv0),
+        }
+    }
+
+    if list {
+        if let Err(e) = device::list_devices() {
+            eprintln!("{argv0}: {e}");
+            std::process::exit(1);
+        }
+        return;
     }
 
-    let action = match args.get(1) {
+    let action = match action_arg {
         Some(action) => {
             if action == "--help" || action == "-h" {
                 usage(argv0);
             }
 
-            match str::parse(action) {
+            match str::parse(&action) {
                 Ok(action) => action,
                 Err(e) => {
                     eprintln!("{argv0}: {e}");
@@ -28,78 +105,121 @@ fn main() {
         None => Action::Get,
     };
 
-    if let Err(e) = action.execute() {
+    let device = match device::resolve_device(device_name.as_deref()) {
+        Ok(device) => device,
+        Err(e) => {
+            eprintln!("{argv0}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut backlight = SysfsBacklight::new(device);
+
+    // `--min N%` is a perceptual percentage: it should mean the same N% `Get` would report, so
+    // convert it through the active `scale` rather than treating it as a raw physical floor.
+    let min_perceptual = if allow_off {
+        0.0
+    } else {
+        f64::from(min_percent) / 100.0
+    };
+    let min_physical = scale.to_physical(min_perceptual);
+
+    if let Err(e) = action.execute(&mut backlight, scale, fade_ms, min_physical) {
         eprintln!("{argv0}: {e}");
         std::process::exit(1);
     }
 }
 
+/// The default `--min` floor, applied to every `Set` unless `--allow-off` is given.
+const DEFAULT_MIN_PERCENT: u8 = 1;
+
 fn usage(argv0: &str) -> ! {
     eprintln!(
-        "Usage: {argv0} [ACTION]
+        "Usage: {argv0} [OPTION]... [ACTION]
 
   ACTION can be:
     +N           increases brightness by N%
     -N           decreases brightness by N%
     N            set brightness to N%
   if no ACTION is given, displays the current brightness level
-  any number N may optionally have a % sign after it"
+  any number N may optionally have a % sign after it
+
+  OPTION can be:
+    --device NAME     operate on the named backlight device instead of the default
+    --list            list available backlight devices and exit
+    --perceptual[=G]  operate in gamma-corrected perceptual space (default gamma 2.2)
+    --exponential K   operate in exponential perceptual space with the given K
+    --fade MS         ramp to the new brightness over MS milliseconds instead of snapping
+    --min N%          never set brightness below N% (default {DEFAULT_MIN_PERCENT}%)
+    --allow-off       allow --min to be bypassed, including dropping to 0%"
     );
 
     std::process::exit(2);
 }
 
 impl Action {
-    /// Executes an action on the system.
-    fn execute(self) -> Result<(), Error> {
-        let max_brightness = read_file_as_u32(PATH_MAX_BRIGHTNESS)?;
-
-        if let Some(percentage) =
-            self.calculate_new_percentage(max_brightness, || read_file_as_u32(PATH_BRIGHTNESS))?
-        {
-            let percentage = percentage.clamp(0.0, 1.0);
-            let new_value = (f64::from(max_brightness) * percentage).round() as u32;
-
-            write_file_from_u32(PATH_BRIGHTNESS, new_value)?;
+    /// Executes an action against a backend, ramping to the result over `fade_ms` milliseconds
+    /// (or snapping immediately when `fade_ms` is `0`).
+    fn execute(
+        self,
+        backlight: &mut dyn Backlight,
+        scale: Scale,
+        fade_ms: u64,
+        min_physical: f64,
+    ) -> Result<(), Error> {
+        if let Some(target_physical) = self.calculate_new_percentage(backlight, scale, min_physical)? {
+            let max_brightness = checked_max(backlight)?;
+
+            if fade_ms == 0 {
+                let target_value = (f64::from(max_brightness) * target_physical).round() as u32;
+                backlight.set(target_value)?;
+            } else {
+                let start_physical = current_physical(backlight)?;
+                fade_to(
+                    backlight,
+                    max_brightness,
+                    scale,
+                    start_physical,
+                    target_physical,
+                    fade_ms,
+                )?;
+            }
         }
 
         Ok(())
     }
 
-    /// Calculates the new percentage of the maximum brightness `action`, given the `max_brightness` and a function to get the current brightness (to allow for testing).
-    /// The `Option<f64>` is the new percentage of `max_brightness` to apply; it should be in the range `0.0..=1.0``.
-    fn calculate_new_percentage<F>(
+    /// Calculates the new physical fraction of `max()` for `self`, given a `backlight` to read the
+    /// current brightness from (and, for `Get`, to report on), the perceptual `scale` in effect,
+    /// and a `min_physical` floor (pass `0.0` to allow driving the panel fully off).
+    /// The `Option<f64>` is the new fraction of `max()` to apply; it is always in `min_physical..=1.0`.
+    fn calculate_new_percentage(
         self,
-        max_brightness: u32,
-        get_brightness: F,
-    ) -> Result<Option<f64>, Error>
-    where
-        F: FnOnce() -> Result<u32, Error>,
-    {
+        backlight: &dyn Backlight,
+        scale: Scale,
+        min_physical: f64,
+    ) -> Result<Option<f64>, Error> {
         match self {
             Action::Set(change, SetMode::RelativeUp) => {
-                let brightness = get_brightness()?;
-
-                let current = f64::from(brightness) / f64::from(max_brightness);
+                let current = scale.to_perceptual(current_physical(backlight)?);
                 let delta = f64::from(change) / 100.0;
 
-                Ok(Some(current + delta))
+                Ok(Some(scale.to_physical(current + delta).max(min_physical)))
             }
             Action::Set(change, SetMode::RelativeDown) => {
-                let brightness = get_brightness()?;
-
-                let current = f64::from(brightness) / f64::from(max_brightness);
+                let current = scale.to_perceptual(current_physical(backlight)?);
                 let delta = f64::from(change) / 100.0;
 
-                Ok(Some(current - delta))
+                Ok(Some(scale.to_physical(current - delta).max(min_physical)))
             }
-            Action::Set(percentage, SetMode::Absolute) => Ok(Some(f64::from(percentage) / 100.0)),
+            Action::Set(percentage, SetMode::Absolute) => Ok(Some(
+                scale
+                    .to_physical(f64::from(percentage) / 100.0)
+                    .max(min_physical),
+            )),
             Action::Get => {
-                let brightness = get_brightness()?;
-                println!(
-                    "{:.0}%",
-                    100.0 * (f64::from(brightness) / f64::from(max_brightness))
-                );
+                let perceptual = scale.to_perceptual(current_physical(backlight)?);
+                println!("{:.0}%", 100.0 * perceptual);
 
                 Ok(None)
             }
@@ -107,36 +227,70 @@ impl Action {
     }
 }
 
-fn read_file_as_u32(path: &str) -> Result<u32, Error> {
-    let mut buf = String::with_capacity(16);
+/// How often `fade_to` writes an intermediate brightness value.
+const FADE_FRAME_MS: u64 = 10;
+
+/// Ramps `backlight` from `start_physical` to `target_physical` over `duration_ms`, writing one
+/// interpolated value per [`FADE_FRAME_MS`]. Interpolation happens in `scale`'s perceptual space,
+/// so the fade looks uniform under `--perceptual`/`--exponential` too; duplicate integer values
+/// are coalesced so we don't hammer the device with redundant writes.
+fn fade_to(
+    backlight: &mut dyn Backlight,
+    max_brightness: u32,
+    scale: Scale,
+    start_physical: f64,
+    target_physical: f64,
+    duration_ms: u64,
+) -> Result<(), Error> {
+    let start_perceptual = scale.to_perceptual(start_physical);
+    let target_perceptual = scale.to_perceptual(target_physical);
+
+    let frames = (duration_ms / FADE_FRAME_MS).max(1);
+    let mut last_written = None;
+
+    for frame in 1..=frames {
+        let t = frame as f64 / frames as f64;
+        let physical = if frame == frames {
+            target_physical
+        } else {
+            let perceptual = start_perceptual + (target_perceptual - start_perceptual) * t;
+            scale.to_physical(perceptual)
+        };
+        let value = (f64::from(max_brightness) * physical).round() as u32;
+
+        if last_written != Some(value) {
+            backlight.set(value)?;
+            last_written = Some(value);
+        }
+
+        if frame != frames {
+            std::thread::sleep(std::time::Duration::from_millis(FADE_FRAME_MS));
+        }
+    }
+
+    Ok(())
+}
 
-    let _read_bytes = std::fs::OpenOptions::new()
-        .read(true)
-        .write(false)
-        .open(path)
-        .map_err(|cause| Error::with_cause(format!("could not read from {path}"), cause))?
-        .read_to_string(&mut buf)
-        .map_err(|cause| Error::with_cause(format!("invalid UTF-8 at {path}"), cause))?;
+/// Reads `backlight`'s current brightness as a physical fraction of its maximum.
+fn current_physical(backlight: &dyn Backlight) -> Result<f64, Error> {
+    let max_brightness = checked_max(backlight)?;
+    let brightness = backlight.get()?;
 
-    let buf = buf.trim();
-    str::parse(buf)
-        .map_err(|cause| Error::with_cause(format!("could not parse '{buf}' as a number"), cause))
+    Ok(f64::from(brightness) / f64::from(max_brightness))
 }
 
-fn write_file_from_u32(path: &str, n: u32) -> Result<(), Error> {
-    write!(
-        std::fs::OpenOptions::new()
-            .read(false)
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .map_err(|cause| Error::with_cause(
-                format!("could not open {path} for writing; try using `sudo` or running `chmod u+s` on the command or `chmod +w on {path}"),
-                cause
-            ))?,
-        "{n}"
-    )
-    .map_err(|cause| Error::with_cause(format!("could not write {n} to {path}"), cause))
+/// Reads `backlight`'s maximum brightness, rejecting a device that reports 0 (which would make
+/// every percentage calculation divide by zero).
+fn checked_max(backlight: &dyn Backlight) -> Result<u32, Error> {
+    let max_brightness = backlight.max()?;
+
+    if max_brightness == 0 {
+        return Err(Error::msg(
+            "backlight device reports a maximum brightness of 0".to_string(),
+        ));
+    }
+
+    Ok(max_brightness)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -162,64 +316,55 @@ impl std::str::FromStr for Action {
             return Ok(Action::Get);
         }
 
-        let (mut s, mode) = match s.chars().next() {
+        let (s, mode) = match s.chars().next() {
             None => return Ok(Action::Get), // should be unreachable, but belt and suspenders
             Some('+') => (&s[1..], SetMode::RelativeUp),
             Some('-') => (&s[1..], SetMode::RelativeDown),
             Some(_) => (s, SetMode::Absolute),
         };
 
-        // drop % at the end
-        if s.ends_with('%') {
-            s = &s[..s.len() - 1];
-        }
+        Ok(Action::Set(parse_percent(s)?, mode))
+    }
+}
 
-        let percentage = str::parse::<u8>(s)
-            .map_err(|cause| Error::with_cause(format!("could not parse '{s}'"), cause))?;
+/// Parses a percentage like `10` or `10%` into `0..=100`.
+fn parse_percent(s: &str) -> Result<u8, Error> {
+    let s = s.strip_suffix('%').unwrap_or(s);
 
-        if percentage > 100 {
-            return Err(Error::msg(format!("'{percentage}' is greater than 100%")));
-        }
+    let percentage = str::parse::<u8>(s)
+        .map_err(|cause| Error::with_cause(format!("could not parse '{s}'"), cause))?;
 
-        Ok(Action::Set(percentage, mode))
+    if percentage > 100 {
+        return Err(Error::msg(format!("'{percentage}' is greater than 100%")));
     }
-}
 
-#[derive(Debug)]
-struct Error {
-    message: String,
-    cause: Option<String>,
+    Ok(percentage)
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)?;
-        if let Some(cause) = &self.cause {
-            write!(f, " ({cause})")?;
-        }
-        Ok(())
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A fake backend for exercising `Action` logic without touching the filesystem.
+    struct FakeBacklight {
+        max: u32,
+        brightness: u32,
     }
-}
 
-impl Error {
-    fn msg(message: String) -> Self {
-        Error {
-            message,
-            cause: None,
+    impl Backlight for FakeBacklight {
+        fn get(&self) -> Result<u32, Error> {
+            Ok(self.brightness)
         }
-    }
 
-    fn with_cause<E: ToString>(message: String, cause: E) -> Self {
-        Error {
-            message: message,
-            cause: Some(cause.to_string()),
+        fn max(&self) -> Result<u32, Error> {
+            Ok(self.max)
         }
-    }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        fn set(&mut self, value: u32) -> Result<(), Error> {
+            self.brightness = value;
+            Ok(())
+        }
+    }
 
     #[test]
     fn test_action_fits_in_usize() {
@@ -314,7 +459,11 @@ mod test {
         assert_eq!(cases.len(), 10504);
 
         for (brightness, action, expected) in cases {
-            match action.calculate_new_percentage(max_brightness, || Ok(brightness)) {
+            let backlight = FakeBacklight {
+                max: max_brightness,
+                brightness,
+            };
+            match action.calculate_new_percentage(&backlight, Scale::Linear, 0.0) {
                 Err(e) => panic!("expected {expected:?} from {action:?} on {brightness}/{max_brightness}, got error {e:?}"),
                 Ok(got) => {
                     let new_brightness = match got {
@@ -329,4 +478,69 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_min_floor_is_honored() {
+        let max_brightness = 100;
+        let min_physical = 0.05;
+
+        let backlight = FakeBacklight {
+            max: max_brightness,
+            brightness: 2,
+        };
+        let got = Action::Set(10, SetMode::RelativeDown)
+            .calculate_new_percentage(&backlight, Scale::Linear, min_physical)
+            .unwrap()
+            .unwrap();
+        assert!(
+            got >= min_physical,
+            "RelativeDown should not drop below the floor, got {got}"
+        );
+
+        let got = Action::Set(0, SetMode::Absolute)
+            .calculate_new_percentage(&backlight, Scale::Linear, min_physical)
+            .unwrap()
+            .unwrap();
+        assert!(
+            got >= min_physical,
+            "an absolute 0 should not drop below the floor, got {got}"
+        );
+
+        // with no floor (--allow-off), both should be allowed to reach 0
+        let got = Action::Set(0, SetMode::Absolute)
+            .calculate_new_percentage(&backlight, Scale::Linear, 0.0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(got, 0.0);
+    }
+
+    #[test]
+    fn test_min_floor_means_the_same_percentage_get_reports_under_perceptual_scale() {
+        // `--min N%` should floor at the same N% that `Get` would report, not at a raw physical
+        // fraction of N% — so the physical floor must be converted through the active scale, the
+        // same way `main` does before calling `Action::execute`.
+        let scale = Scale::Perceptual { gamma: 2.2 };
+        let min_perceptual = 0.10;
+        let min_physical = scale.to_physical(min_perceptual);
+
+        // sanity check: converting the floor back to perceptual space should recover ~10%, not
+        // whatever raw-physical 10% would have been under this gamma
+        assert!((scale.to_perceptual(min_physical) - min_perceptual).abs() < 1e-9);
+
+        let backlight = FakeBacklight {
+            max: 100,
+            brightness: 0,
+        };
+
+        let got = Action::Set(100, SetMode::RelativeDown)
+            .calculate_new_percentage(&backlight, scale, min_physical)
+            .unwrap()
+            .unwrap();
+
+        let reported_percent = scale.to_perceptual(got) * 100.0;
+        assert!(
+            (reported_percent - 10.0).abs() < 1e-6,
+            "expected the floor to read back as 10%, got {reported_percent}%"
+        );
+    }
 }
@@ -0,0 +1,148 @@
+/// How a user-facing percentage maps onto the physical brightness fraction written to the device.
+///
+/// Human brightness perception is roughly logarithmic: a linear step near the bottom of the
+/// range looks huge, while the same step near the top is barely visible. The non-linear modes
+/// let `+N`/`-N`/`N` operate in perceived space instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Scale {
+    /// The percentage is the physical fraction directly.
+    Linear,
+    /// Gamma correction: `f = p^gamma`, inverted by `p = f^(1/gamma)`.
+    Perceptual { gamma: f64 },
+    /// Exponential: `f = (exp(k*p) - 1) / (exp(k) - 1)`, inverted by `p = ln(f*(exp(k)-1)+1)/k`.
+    Exponential { k: f64 },
+}
+
+impl Scale {
+    /// The default gamma used by `--perceptual` when no value is given.
+    pub(crate) const DEFAULT_GAMMA: f64 = 2.2;
+
+    /// Maps a perceptual percentage `p` (in `0.0..=1.0`) to the physical fraction to write to the device.
+    pub(crate) fn to_physical(self, p: f64) -> f64 {
+        let p = p.clamp(0.0, 1.0);
+
+        let f = match self {
+            Scale::Linear => p,
+            Scale::Perceptual { gamma } => p.powf(gamma),
+            // as k -> 0 the exponential curve's limit is linear; guard the removable
+            // singularity at k == 0 explicitly rather than propagating a 0/0 NaN.
+            Scale::Exponential { k: 0.0 } => p,
+            Scale::Exponential { k } => (f64::exp(k * p) - 1.0) / (f64::exp(k) - 1.0),
+        };
+
+        f.clamp(0.0, 1.0)
+    }
+
+    /// Maps a physical fraction `f` (in `0.0..=1.0`) back to the perceptual percentage a user would perceive.
+    pub(crate) fn to_perceptual(self, f: f64) -> f64 {
+        let f = f.clamp(0.0, 1.0);
+
+        let p = match self {
+            Scale::Linear => f,
+            Scale::Perceptual { gamma } => f.powf(1.0 / gamma),
+            Scale::Exponential { k: 0.0 } => f,
+            Scale::Exponential { k } => (f * (f64::exp(k) - 1.0) + 1.0).ln() / k,
+        };
+
+        p.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_linear_round_trip() {
+        for i in 0..=100 {
+            let p = f64::from(i) / 100.0;
+            assert_eq!(Scale::Linear.to_physical(p), p);
+            assert_eq!(Scale::Linear.to_perceptual(p), p);
+        }
+    }
+
+    #[test]
+    fn test_perceptual_round_trip() {
+        for gamma in [1.0, 1.8, 2.2, 2.6, 3.0] {
+            let scale = Scale::Perceptual { gamma };
+
+            for i in 0..=100 {
+                let p = f64::from(i) / 100.0;
+                let f = scale.to_physical(p);
+                let round_tripped = scale.to_perceptual(f);
+
+                assert!(
+                    (round_tripped - p).abs() < 1e-9,
+                    "gamma {gamma}: expected {p} to round-trip, got {round_tripped} (via f={f})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponential_round_trip() {
+        for k in [0.5, 1.0, 3.0, 6.0] {
+            let scale = Scale::Exponential { k };
+
+            for i in 0..=100 {
+                let p = f64::from(i) / 100.0;
+                let f = scale.to_physical(p);
+                let round_tripped = scale.to_perceptual(f);
+
+                assert!(
+                    (round_tripped - p).abs() < 1e-9,
+                    "k {k}: expected {p} to round-trip, got {round_tripped} (via f={f})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponential_k_zero_behaves_like_linear() {
+        let scale = Scale::Exponential { k: 0.0 };
+
+        for i in 0..=100 {
+            let p = f64::from(i) / 100.0;
+            assert_eq!(scale.to_physical(p), p, "k=0 should map {p} to itself");
+            assert_eq!(scale.to_perceptual(p), p, "k=0 should invert {p} to itself");
+        }
+    }
+
+    #[test]
+    fn test_endpoints_are_fixed() {
+        let scales = [
+            Scale::Linear,
+            Scale::Perceptual {
+                gamma: Scale::DEFAULT_GAMMA,
+            },
+            Scale::Exponential { k: 3.0 },
+        ];
+
+        for scale in scales {
+            assert_eq!(scale.to_physical(0.0), 0.0, "{scale:?} should map 0 to 0");
+            assert_eq!(scale.to_physical(1.0), 1.0, "{scale:?} should map 1 to 1");
+            assert_eq!(
+                scale.to_perceptual(0.0),
+                0.0,
+                "{scale:?} should invert 0 to 0"
+            );
+            assert_eq!(
+                scale.to_perceptual(1.0),
+                1.0,
+                "{scale:?} should invert 1 to 1"
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_inputs_are_clamped() {
+        let scale = Scale::Perceptual {
+            gamma: Scale::DEFAULT_GAMMA,
+        };
+
+        assert_eq!(scale.to_physical(-0.5), 0.0);
+        assert_eq!(scale.to_physical(1.5), 1.0);
+        assert_eq!(scale.to_perceptual(-0.5), 0.0);
+        assert_eq!(scale.to_perceptual(1.5), 1.0);
+    }
+}